@@ -0,0 +1,55 @@
+use crate::{extract, siteinfo};
+use err_derive::Error;
+use serde::{Deserialize, Serialize};
+use std::collections;
+
+pub use extract::{LinkTrailError, MalformedExtensionTagError, NamespaceNotFoundError};
+
+#[derive(Debug, Error)]
+pub enum ConfigurationError {
+    #[error(display = "{}", _0)]
+    Namespace(#[error(source)] extract::NamespaceNotFoundError),
+    #[error(display = "{}", _0)]
+    ExtensionTag(#[error(source)] extract::MalformedExtensionTagError),
+    #[error(display = "{}", _0)]
+    LinkTrail(#[error(source)] extract::LinkTrailError),
+}
+
+/// A MediaWiki site's configuration, extracted from a `siteinfo` API response.
+///
+/// This is the public entry point for using `fetch_mediawiki_configuration` as a library: fetch
+/// a [`siteinfo::response::Query`] once, build a `Configuration` from it, then serialize it to
+/// cache it for offline use without re-hitting the MediaWiki API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Configuration {
+    pub category_namespaces: collections::BTreeSet<String>,
+    pub file_namespaces: collections::BTreeSet<String>,
+    pub template_namespaces: collections::BTreeSet<String>,
+    pub extension_tags: collections::BTreeSet<String>,
+    pub protocols: collections::BTreeSet<String>,
+    pub link_trail: collections::BTreeSet<char>,
+    pub magic_words: collections::BTreeSet<String>,
+    pub magic_words_redirect: collections::BTreeSet<String>,
+}
+
+impl Configuration {
+    pub fn new(query: &siteinfo::response::Query) -> Result<Self, ConfigurationError> {
+        let mut namespace_sets = extract::namespace_sets(query)?;
+        Ok(Configuration {
+            category_namespaces: namespace_sets
+                .remove(extract::CATEGORY_NAMESPACE)
+                .expect("set for well-known namespace"),
+            file_namespaces: namespace_sets
+                .remove(extract::FILE_NAMESPACE)
+                .expect("set for well-known namespace"),
+            template_namespaces: namespace_sets
+                .remove(extract::TEMPLATE_NAMESPACE)
+                .expect("set for well-known namespace"),
+            extension_tags: extract::extension_tags(query)?,
+            protocols: extract::protocols(query),
+            link_trail: extract::link_trail(query)?,
+            magic_words: extract::magic_words(query),
+            magic_words_redirect: extract::magic_words_redirect(query),
+        })
+    }
+}
@@ -6,14 +6,14 @@ use std::{collections, iter};
 
 #[derive(Debug, Error)]
 #[error(display = "namespace not found: {:?}", _0)]
-pub(crate) struct NamespaceNotFoundError(String);
+pub struct NamespaceNotFoundError(String);
 
 #[derive(Debug, Error)]
 #[error(display = "malformed extension tag: {:?}", _0)]
-pub(crate) struct MalformedExtensionTagError(String);
+pub struct MalformedExtensionTagError(String);
 
 #[derive(Debug, Error)]
-pub(crate) enum LinkTrailError {
+pub enum LinkTrailError {
     #[error(display = "{}", _0)]
     PCRE(#[error(source)] pcre::PatternParseError),
     #[error(
@@ -30,6 +30,54 @@ pub(crate) enum LinkTrailError {
     GroupInvalid { pattern: String, index: u32 },
 }
 
+pub(crate) const CATEGORY_NAMESPACE: &str = "Category";
+pub(crate) const FILE_NAMESPACE: &str = "File";
+pub(crate) const TEMPLATE_NAMESPACE: &str = "Template";
+
+const WELL_KNOWN_NAMESPACES: [&str; 3] = [CATEGORY_NAMESPACE, FILE_NAMESPACE, TEMPLATE_NAMESPACE];
+
+/// Builds the lowercased name/alias set for each of [`WELL_KNOWN_NAMESPACES`] in one traversal
+/// of `query.namespaces` and one traversal of `query.namespacealiases`, rather than re-scanning
+/// both for every canonical name as [`namespaces`] does.
+pub(crate) fn namespace_sets(
+    query: &siteinfo::response::Query,
+) -> Result<collections::BTreeMap<&'static str, collections::BTreeSet<String>>, NamespaceNotFoundError>
+{
+    let mut ids = collections::BTreeMap::new();
+    let mut sets: collections::BTreeMap<_, _> = WELL_KNOWN_NAMESPACES
+        .iter()
+        .map(|&canonical| (canonical, collections::BTreeSet::new()))
+        .collect();
+
+    for namespace in query.namespaces.values() {
+        if let Some(&canonical) = WELL_KNOWN_NAMESPACES
+            .iter()
+            .find(|&&canonical| namespace.canonical.as_ref().map(AsRef::as_ref) == Some(canonical))
+        {
+            ids.insert(namespace.id, canonical);
+            let set = sets.get_mut(canonical).expect("set for well-known namespace");
+            set.insert(canonical.to_lowercase());
+            set.insert(namespace.name.to_lowercase());
+        }
+    }
+
+    for canonical in WELL_KNOWN_NAMESPACES {
+        if !ids.values().any(|&found| found == canonical) {
+            return Err(NamespaceNotFoundError(canonical.to_owned()));
+        }
+    }
+
+    for alias in &query.namespacealiases {
+        if let Some(&canonical) = ids.get(&alias.id) {
+            sets.get_mut(canonical)
+                .expect("set for well-known namespace")
+                .insert(alias.alias.to_lowercase());
+        }
+    }
+
+    Ok(sets)
+}
+
 pub(crate) fn namespaces(
     query: &siteinfo::response::Query,
     canonical: &str,
@@ -74,8 +122,6 @@ pub(crate) fn protocols(query: &siteinfo::response::Query) -> collections::BTree
 pub(crate) fn link_trail(
     query: &siteinfo::response::Query,
 ) -> Result<collections::BTreeSet<char>, LinkTrailError> {
-    use hir::HirKind::*;
-
     let original = &query.general.linktrail;
     let pattern: pcre::Pattern = original.parse().map_err(LinkTrailError::PCRE)?;
     log::debug!("pattern = {:?}", pattern);
@@ -89,26 +135,15 @@ pub(crate) fn link_trail(
                 pattern: original.clone(),
                 index: GROUP_INDEX,
             })?;
-    let repeated = match group.hir.kind() {
-        Empty => Ok(None),
-        Repetition(repetition) => Ok(Some(&repetition.hir)),
-        Alternation(..) | Anchor(..) | Class(..) | Concat(..) | Group(..) | Literal(..)
-        | WordBoundary(..) => Err(LinkTrailError::GroupInvalid {
-            pattern: original.clone(),
-            index: GROUP_INDEX,
-        }),
-    }?;
-    log::debug!("repeated = {:?}", repeated.map(|r| pcre::HirDebugAlt(r)));
+    log::debug!("group = {:?}", pcre::HirDebugAlt(&group.hir));
 
     let mut characters = Default::default();
-    if let Some(repeated) = repeated {
-        link_trail_characters(repeated, &mut characters).map_err(|_| {
-            LinkTrailError::GroupInvalid {
-                pattern: original.clone(),
-                index: GROUP_INDEX,
-            }
-        })?;
-    }
+    link_trail_characters(&group.hir, &mut characters).map_err(|_| {
+        LinkTrailError::GroupInvalid {
+            pattern: original.clone(),
+            index: GROUP_INDEX,
+        }
+    })?;
     Ok(characters)
 }
 
@@ -145,16 +180,27 @@ fn link_trail_characters(
             }
             Ok(())
         }
+        Concat(hirs) => {
+            for hir in hirs {
+                link_trail_characters(hir, characters)?;
+            }
+            Ok(())
+        }
         Group(group) => link_trail_characters(&group.hir, characters),
         Literal(literal) => {
-            let c = match literal {
-                Literal::Byte(..) => unreachable!(),
-                Literal::Unicode(c) => *c,
-            };
-            characters.insert(c);
+            match literal {
+                Literal::Byte(b) => {
+                    debug_assert!(b.is_ascii());
+                    characters.insert((*b).into());
+                }
+                Literal::Unicode(c) => {
+                    characters.insert(*c);
+                }
+            }
             Ok(())
         }
-        Anchor(..) | Concat(..) | Empty | Repetition(..) | WordBoundary(..) => Err(()),
+        Repetition(repetition) => link_trail_characters(&repetition.hir, characters),
+        Anchor(..) | Empty | WordBoundary(..) => Ok(()),
     }
 }
 